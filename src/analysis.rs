@@ -0,0 +1,190 @@
+use std::collections::{HashMap, VecDeque};
+
+use pflow_metamodel::petri_net::PetriNet;
+use serde::Serialize;
+
+/// A place's token count in a marking: either a finite count or `Omega`, the
+/// unbounded marker produced by Karp-Miller widening.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Token {
+    Finite(i64),
+    Omega,
+}
+
+impl Token {
+    fn covers(self, other: Token) -> bool {
+        match (self, other) {
+            (Token::Omega, _) => true,
+            (Token::Finite(a), Token::Finite(b)) => a >= b,
+            (Token::Finite(_), Token::Omega) => false,
+        }
+    }
+
+    fn add(self, delta: i64) -> Token {
+        match self {
+            Token::Omega => Token::Omega,
+            Token::Finite(n) => Token::Finite(n + delta),
+        }
+    }
+}
+
+pub type Marking = Vec<Token>;
+
+#[derive(Debug, Serialize)]
+pub struct CoverabilityEdge {
+    pub from: usize,
+    pub to: usize,
+    pub transition: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CoverabilityGraph {
+    pub places: Vec<String>,
+    pub markings: Vec<Marking>,
+    pub edges: Vec<CoverabilityEdge>,
+    pub dead_markings: Vec<usize>,
+    pub bounded: bool,
+}
+
+/// Builds the Karp-Miller coverability graph for `net`, starting from its
+/// initial marking. Explores reachable markings breadth-first; whenever a new
+/// marking covers an ancestor on its path (componentwise `>=`, and not equal),
+/// the exceeding coordinates are widened to `omega`, which is then treated as
+/// absorbing under addition, subtraction, and comparison. This guarantees
+/// termination even when the underlying net is unbounded.
+pub fn coverability_graph(net: &PetriNet) -> CoverabilityGraph {
+    let place_ids: Vec<String> = net.places.iter().map(|p| p.id.clone()).collect();
+    let place_index: HashMap<&str, usize> = place_ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+
+    let initial: Marking = net
+        .places
+        .iter()
+        .map(|p| Token::Finite(p.initial as i64))
+        .collect();
+
+    let mut markings: Vec<Marking> = vec![initial.clone()];
+    let mut edges = Vec::new();
+    let mut dead_markings = Vec::new();
+    // path[i] holds the ancestry (as indices into `markings`) from the root to i.
+    let mut path: Vec<Vec<usize>> = vec![vec![0]];
+    let mut queue: VecDeque<usize> = VecDeque::from([0]);
+
+    while let Some(current_idx) = queue.pop_front() {
+        let current = markings[current_idx].clone();
+        let mut fired_any = false;
+
+        for transition in &net.transitions {
+            if let Some(next) = try_fire(&place_index, &current, net, &transition.id) {
+                fired_any = true;
+                let mut widened = next;
+                for &ancestor_idx in &path[current_idx] {
+                    let ancestor = &markings[ancestor_idx];
+                    if covers_and_differs(&widened, ancestor) {
+                        widen(&mut widened, ancestor);
+                    }
+                }
+
+                let target_idx = match markings.iter().position(|m| m == &widened) {
+                    Some(idx) => idx,
+                    None => {
+                        markings.push(widened.clone());
+                        let mut ancestry = path[current_idx].clone();
+                        ancestry.push(markings.len() - 1);
+                        path.push(ancestry);
+                        queue.push_back(markings.len() - 1);
+                        markings.len() - 1
+                    }
+                };
+
+                edges.push(CoverabilityEdge {
+                    from: current_idx,
+                    to: target_idx,
+                    transition: transition.id.clone(),
+                });
+            }
+        }
+
+        if !fired_any {
+            dead_markings.push(current_idx);
+        }
+    }
+
+    let bounded = markings
+        .iter()
+        .all(|m| m.iter().all(|t| !matches!(t, Token::Omega)));
+
+    CoverabilityGraph {
+        places: place_ids,
+        markings,
+        edges,
+        dead_markings,
+        bounded,
+    }
+}
+
+fn covers_and_differs(marking: &Marking, ancestor: &Marking) -> bool {
+    let covers = marking
+        .iter()
+        .zip(ancestor.iter())
+        .all(|(m, a)| m.covers(*a));
+    covers && marking != ancestor
+}
+
+fn widen(marking: &mut Marking, ancestor: &Marking) {
+    for (m, a) in marking.iter_mut().zip(ancestor.iter()) {
+        let strictly_greater = match (*m, *a) {
+            (Token::Finite(x), Token::Finite(y)) => x > y,
+            (Token::Omega, Token::Finite(_)) => true,
+            _ => false,
+        };
+        if strictly_greater {
+            *m = Token::Omega;
+        }
+    }
+}
+
+fn try_fire(
+    place_index: &HashMap<&str, usize>,
+    marking: &Marking,
+    net: &PetriNet,
+    transition_id: &str,
+) -> Option<Marking> {
+    let mut next = marking.clone();
+
+    for arc in &net.arcs {
+        if arc.target != transition_id {
+            continue;
+        }
+        let idx = *place_index.get(arc.source.as_str())?;
+        if arc.inhibit {
+            if !matches!(marking[idx], Token::Finite(n) if n < arc.weight as i64) {
+                return None;
+            }
+        } else if !marking[idx].covers(Token::Finite(arc.weight as i64)) {
+            return None;
+        }
+    }
+
+    for arc in &net.arcs {
+        if arc.source != transition_id || arc.inhibit {
+            continue;
+        }
+        if let Some(&idx) = place_index.get(arc.target.as_str()) {
+            next[idx] = next[idx].add(arc.weight as i64);
+        }
+    }
+    for arc in &net.arcs {
+        if arc.target == transition_id && !arc.inhibit {
+            if let Some(&idx) = place_index.get(arc.source.as_str()) {
+                next[idx] = next[idx].add(-(arc.weight as i64));
+            }
+        }
+    }
+
+    Some(next)
+}
@@ -0,0 +1,97 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Decides whether a bearer token/API key may perform a write. GET/read
+/// routes never consult this; only the handlers that mutate storage do.
+/// Modeled after proxmox-backup's generic `ApiAuth`, so deployments can swap
+/// the static config-file verifier below for one backed by a real identity
+/// service later.
+pub trait ApiAuth: Send + Sync {
+    fn is_authorized(&self, api_key: &str) -> bool;
+}
+
+/// Verifies API keys against a fixed set loaded from a config file, one key
+/// per line (blank lines and `#`-prefixed comments are ignored).
+pub struct StaticKeyAuth {
+    keys: HashSet<String>,
+}
+
+impl StaticKeyAuth {
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let keys = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Ok(StaticKeyAuth { keys })
+    }
+
+    pub fn from_keys<I: IntoIterator<Item = String>>(keys: I) -> Self {
+        StaticKeyAuth {
+            keys: keys.into_iter().collect(),
+        }
+    }
+}
+
+impl ApiAuth for StaticKeyAuth {
+    fn is_authorized(&self, api_key: &str) -> bool {
+        self.keys.contains(api_key)
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A per-key token-bucket rate limiter for write traffic. Each key gets its
+/// own bucket of `capacity` tokens that refill at `refill_per_sec`; a write
+/// is allowed only while its bucket holds at least one token.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        RateLimiter {
+            capacity,
+            refill_per_sec,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Attempts to consume one token for `key`, returning `false` (and
+    /// consuming nothing) if the bucket is empty.
+    pub fn try_acquire(&self, key: &str) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key.to_string()).or_insert_with(|| TokenBucket {
+            tokens: self.capacity,
+            last_refill: Instant::now(),
+        });
+
+        let elapsed = bucket.last_refill.elapsed();
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = Instant::now();
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        // 30 writes/minute per key, refilling continuously.
+        RateLimiter::new(30.0, 30.0 / Duration::from_secs(60).as_secs_f64())
+    }
+}
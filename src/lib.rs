@@ -0,0 +1,5 @@
+pub mod analysis;
+pub mod auth;
+pub mod rendering;
+pub mod server;
+pub mod storage;
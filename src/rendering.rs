@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use pflow_metamodel::petri_net::PetriNet;
+
+const PADDING: f64 = 40.0;
+const GRID_SPACING: f64 = 120.0;
+const PLACE_RADIUS: f64 = 20.0;
+const TRANSITION_SIZE: f64 = 36.0;
+
+struct Layout {
+    places: HashMap<String, (f64, f64)>,
+    transitions: HashMap<String, (f64, f64)>,
+}
+
+/// Renders a `PetriNet` to an SVG document.
+///
+/// Places become labeled circles showing their token count, transitions become
+/// rectangles, normal arcs become arrows (annotated with weight when >1), and
+/// inhibitor arcs terminate in a small hollow circle instead of an arrowhead.
+/// Coordinates come from the model's `x`/`y` fields; only when every node sits
+/// at the origin (the model carries no coordinates) are all nodes laid out on
+/// a grid instead.
+pub fn render_svg(net: &PetriNet) -> String {
+    let layout = build_layout(net);
+    let (min_x, min_y, max_x, max_y) = bounding_box(&layout);
+    let view_x = min_x - PADDING;
+    let view_y = min_y - PADDING;
+    let view_w = (max_x - min_x) + PADDING * 2.0;
+    let view_h = (max_y - min_y) + PADDING * 2.0;
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" font-family="sans-serif" font-size="12">"#,
+        view_x, view_y, view_w, view_h
+    ));
+    svg.push_str(
+        r#"<defs>
+  <marker id="arrow" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="8" markerHeight="8" orient="auto-start-reverse">
+    <path d="M 0 0 L 10 5 L 0 10 z" fill="#333"/>
+  </marker>
+</defs>"#,
+    );
+
+    for arc in &net.arcs {
+        let Some(&from) = source_point(&layout, &arc.source) else { continue };
+        let Some(&to) = target_point(&layout, &arc.target) else { continue };
+        svg.push_str(&render_arc(from, to, arc.weight, arc.inhibit));
+    }
+
+    for place in &net.places {
+        let (x, y) = layout.places[&place.id];
+        svg.push_str(&render_place(x, y, &place.id, place.initial));
+    }
+
+    for transition in &net.transitions {
+        let (x, y) = layout.transitions[&transition.id];
+        svg.push_str(&render_transition(x, y, &transition.id));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn source_point<'a>(layout: &'a Layout, id: &str) -> Option<&'a (f64, f64)> {
+    layout.places.get(id).or_else(|| layout.transitions.get(id))
+}
+
+fn target_point<'a>(layout: &'a Layout, id: &str) -> Option<&'a (f64, f64)> {
+    layout.places.get(id).or_else(|| layout.transitions.get(id))
+}
+
+fn build_layout(net: &PetriNet) -> Layout {
+    let mut places = HashMap::new();
+    let mut transitions = HashMap::new();
+
+    // A single node sitting at (0, 0) is a legitimate layout choice, not a
+    // sign the model has no coordinates at all — so the fallback to a grid
+    // is a net-wide decision (every node at the origin), not a per-node one.
+    // That keeps a node placed at the origin from being bounced to the grid
+    // while its neighbors keep their real coordinates.
+    let model_has_coords = net.places.iter().any(|p| p.x != 0.0 || p.y != 0.0)
+        || net.transitions.iter().any(|t| t.x != 0.0 || t.y != 0.0);
+
+    let mut grid_index = 0usize;
+
+    for place in &net.places {
+        let (x, y) = if model_has_coords {
+            (place.x, place.y)
+        } else {
+            grid_index += 1;
+            grid_position(grid_index)
+        };
+        places.insert(place.id.clone(), (x, y));
+    }
+
+    for transition in &net.transitions {
+        let (x, y) = if model_has_coords {
+            (transition.x, transition.y)
+        } else {
+            grid_index += 1;
+            grid_position(grid_index)
+        };
+        transitions.insert(transition.id.clone(), (x, y));
+    }
+
+    Layout { places, transitions }
+}
+
+fn grid_position(index: usize) -> (f64, f64) {
+    let columns = 6;
+    let col = (index % columns) as f64;
+    let row = (index / columns) as f64;
+    (col * GRID_SPACING, row * GRID_SPACING)
+}
+
+fn bounding_box(layout: &Layout) -> (f64, f64, f64, f64) {
+    let points = layout.places.values().chain(layout.transitions.values());
+    let mut min_x = 0.0_f64;
+    let mut min_y = 0.0_f64;
+    let mut max_x = GRID_SPACING;
+    let mut max_y = GRID_SPACING;
+    for &(x, y) in points {
+        min_x = min_x.min(x - PLACE_RADIUS);
+        min_y = min_y.min(y - PLACE_RADIUS);
+        max_x = max_x.max(x + PLACE_RADIUS);
+        max_y = max_y.max(y + PLACE_RADIUS);
+    }
+    (min_x, min_y, max_x, max_y)
+}
+
+fn render_place(x: f64, y: f64, id: &str, tokens: u64) -> String {
+    format!(
+        r#"<g><circle cx="{x}" cy="{y}" r="{r}" fill="#fff" stroke="#333" stroke-width="1.5"/>
+<text x="{x}" y="{ty}" text-anchor="middle">{tokens}</text>
+<text x="{x}" y="{ly}" text-anchor="middle" font-size="10">{id}</text></g>"#,
+        x = x,
+        y = y,
+        r = PLACE_RADIUS,
+        ty = y + 4.0,
+        tokens = tokens,
+        ly = y + PLACE_RADIUS + 12.0,
+        id = escape_xml_text(id),
+    )
+}
+
+fn render_transition(x: f64, y: f64, id: &str) -> String {
+    let half = TRANSITION_SIZE / 2.0;
+    format!(
+        r#"<g><rect x="{rx}" y="{ry}" width="{w}" height="{w}" fill="#fff" stroke="#333" stroke-width="1.5"/>
+<text x="{x}" y="{ly}" text-anchor="middle" font-size="10">{id}</text></g>"#,
+        rx = x - half,
+        ry = y - half,
+        w = TRANSITION_SIZE,
+        x = x,
+        ly = y + half + 12.0,
+        id = escape_xml_text(id),
+    )
+}
+
+/// Escapes text interpolated into SVG text nodes. Place/transition ids come
+/// straight from the model (and ultimately from whoever uploaded it), so an
+/// id like `</text><script>` must not be able to break out of its element.
+fn escape_xml_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn render_arc(from: (f64, f64), to: (f64, f64), weight: u64, inhibit: bool) -> String {
+    let (x1, y1) = from;
+    // Nodes are drawn over their arcs, so stop short of the target's center
+    // by enough to clear a place's circle or a transition's corners; otherwise
+    // the arrowhead or inhibitor circle is hidden under the node.
+    let clearance = PLACE_RADIUS.max(TRANSITION_SIZE / 2.0 * std::f64::consts::SQRT_2);
+    let (x2, y2) = point_towards(from, to, clearance);
+    let mut out = String::new();
+    if inhibit {
+        let (hx, hy) = point_towards(from, to, clearance + 4.0);
+        out.push_str(&format!(
+            r#"<line x1="{x1}" y1="{y1}" x2="{hx}" y2="{hy}" stroke="#333" stroke-width="1.5"/>"#,
+        ));
+        out.push_str(&format!(
+            r#"<circle cx="{hx}" cy="{hy}" r="4" fill="#fff" stroke="#333" stroke-width="1.5"/>"#,
+        ));
+    } else {
+        out.push_str(&format!(
+            r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="#333" stroke-width="1.5" marker-end="url(#arrow)"/>"#,
+        ));
+    }
+    if weight > 1 {
+        let (mx, my) = midpoint(from, to);
+        out.push_str(&format!(
+            r#"<text x="{mx}" y="{my}" text-anchor="middle" font-size="10" fill="#333">{weight}</text>"#,
+        ));
+    }
+    out
+}
+
+fn midpoint(from: (f64, f64), to: (f64, f64)) -> (f64, f64) {
+    ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0)
+}
+
+fn point_towards(from: (f64, f64), to: (f64, f64), distance_from_to: f64) -> (f64, f64) {
+    let dx = to.0 - from.0;
+    let dy = to.1 - from.1;
+    let len = (dx * dx + dy * dy).sqrt().max(1.0);
+    (
+        to.0 - dx / len * distance_from_to,
+        to.1 - dy / len * distance_from_to,
+    )
+}
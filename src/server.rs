@@ -7,26 +7,65 @@ use std::ops::Deref;
 use std::sync::Mutex;
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    body::to_bytes,
+    extract::{FromRequest, Multipart, Path, Query, Request, State},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
     response::{IntoResponse, Redirect, Response},
+    Json,
     Router,
     routing::get,
 };
 use clap::Parser;
-use pflow_metamodel::compression::unzip_encoded;
+use pflow_metamodel::compression::{unzip_encoded, zip_encoded};
 use pflow_metamodel::oid;
 use pflow_metamodel::petri_net::PetriNet;
+use tower_http::compression::{predicate::SizeAbove, CompressionLayer};
 use tower_http::trace::TraceLayer;
 
-use crate::storage::{Storage, Zblob};
+use crate::analysis::coverability_graph;
+use crate::auth::{ApiAuth, RateLimiter, StaticKeyAuth};
+use crate::rendering::render_svg;
+use crate::storage::{ModelStore, SearchQuery, Storage, Zblob};
+
+/// Shared handle to whatever `ModelStore` backend the server was started with.
+pub type SharedStore = Arc<Mutex<dyn ModelStore>>;
+
+/// Everything a handler needs: the model store plus the auth/rate-limit
+/// pair guarding write routes. Cheap to clone — every field is an `Arc`.
+#[derive(Clone)]
+pub struct AppState {
+    pub store: SharedStore,
+    pub auth: Arc<dyn ApiAuth>,
+    pub rate_limiter: Arc<RateLimiter>,
+}
+
+/// Checks the `Authorization: Bearer <key>` header against `state.auth` and
+/// consumes one token from that key's rate-limit bucket. Only write handlers
+/// call this; GET/read routes remain public.
+fn authorize_write(headers: &HeaderMap, state: &AppState) -> Result<(), Response> {
+    let api_key = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    let Some(api_key) = api_key else {
+        return Err(StatusCode::UNAUTHORIZED.into_response());
+    };
+    if !state.auth.is_authorized(api_key) {
+        return Err(StatusCode::UNAUTHORIZED.into_response());
+    }
+    if !state.rate_limiter.try_acquire(api_key) {
+        return Err(StatusCode::TOO_MANY_REQUESTS.into_response());
+    }
+    Ok(())
+}
 
 
 async fn src_handler(
     Path(ipfs_cid): Path<String>,
-    state: State<Arc<Mutex<Storage>>>,
+    state: State<AppState>,
 ) -> impl IntoResponse {
-    let zblob = state.lock().unwrap()
+    let zblob = state.store.lock().unwrap()
         .get_by_cid("pflow_models", &*ipfs_cid)
         .unwrap_or(Option::from(Zblob::default()))
         .unwrap_or(Zblob::default());
@@ -44,21 +83,163 @@ async fn src_handler(
 
 async fn img_handler(
     Path(ipfs_cid): Path<String>,
-    state: State<Arc<Mutex<Storage>>>,
+    state: State<AppState>,
 ) -> impl IntoResponse {
-    let zblob = state.lock().unwrap()
+    let zblob = state.store.lock().unwrap()
         .get_by_cid("pflow_models", &*ipfs_cid)
         .unwrap_or(Option::from(Zblob::default()))
         .unwrap_or(Zblob::default());
 
-    let data = unzip_encoded(&zblob.base64_zipped, "model.json").unwrap_or("".to_string());
-    let content_type = "application/json charset=utf-8";
-    let status = StatusCode::OK;
+    let json = unzip_encoded(&zblob.base64_zipped, "model.json").unwrap_or("".to_string());
+    let net = match PetriNet::from_json(json) {
+        Ok(net) => net,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    };
+
     Response::builder()
-        .status(status)
-        .header("Content-Type", content_type)
-        .body(data)
+        .status(StatusCode::OK)
+        .header("Content-Type", "image/svg+xml")
+        .body(render_svg(&net))
         .unwrap()
+        .into_response()
+}
+
+async fn analyze_handler(
+    Path(ipfs_cid): Path<String>,
+    state: State<AppState>,
+) -> impl IntoResponse {
+    let zblob = state.store.lock().unwrap()
+        .get_by_cid("pflow_models", &*ipfs_cid)
+        .unwrap_or(Option::from(Zblob::default()))
+        .unwrap_or(Zblob::default());
+
+    let json = unzip_encoded(&zblob.base64_zipped, "model.json").unwrap_or("".to_string());
+    match PetriNet::from_json(json) {
+        Ok(net) => Json(coverability_graph(&net)).into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Caps how much of a model upload we'll buffer in memory, so a single
+/// authorized key can't OOM the process with an oversized body.
+const MAX_UPLOAD_BYTES: usize = 8 * 1024 * 1024;
+
+/// Accepts a raw `PetriNet` model, either as a multipart file field named
+/// `file` or as a raw `application/json` body. The model is validated by
+/// parsing it, then stored the way a `z=`-carrying request's blob is: zipped
+/// and content-addressed.
+async fn upload_handler(state: State<AppState>, req: Request) -> impl IntoResponse {
+    if let Err(response) = authorize_write(req.headers(), &state) {
+        return response;
+    }
+
+    let is_multipart = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|ct| ct.starts_with("multipart/form-data"))
+        .unwrap_or(false);
+
+    let json = if is_multipart {
+        match read_multipart_model_json(req, &state).await {
+            Ok(json) => json,
+            Err(response) => return response,
+        }
+    } else {
+        match to_bytes(req.into_body(), MAX_UPLOAD_BYTES).await {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+            Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+        }
+    };
+
+    if PetriNet::from_json(json.clone()).is_err() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, "not a valid PetriNet model").into_response();
+    }
+
+    let base64_zipped = zip_encoded(&json, "model.json");
+    let ipfs_cid = match oid::Oid::new(base64_zipped.as_bytes()) {
+        Ok(oid) => oid.to_string(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    let stored = state.store.lock().unwrap().create_or_retrieve(
+        "pflow_models",
+        &ipfs_cid,
+        &base64_zipped,
+        "",
+        "",
+        "",
+        "",
+    );
+
+    match stored {
+        Ok(zblob) => Response::builder()
+            .status(StatusCode::CREATED)
+            .header("Location", format!("/p/{}/", zblob.ipfs_cid))
+            .body(zblob.ipfs_cid)
+            .unwrap()
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn read_multipart_model_json(req: Request, state: &AppState) -> Result<String, Response> {
+    let mut multipart = Multipart::from_request(req, state)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some("file") {
+            let bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+            if bytes.len() > MAX_UPLOAD_BYTES {
+                return Err(StatusCode::PAYLOAD_TOO_LARGE.into_response());
+            }
+            return Ok(String::from_utf8_lossy(&bytes).to_string());
+        }
+    }
+
+    Err((StatusCode::BAD_REQUEST, "missing \"file\" field").into_response())
+}
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+async fn search_handler(
+    req: Query<HashMap<String, String>>,
+    state: State<AppState>,
+) -> impl IntoResponse {
+    // Clamp here, once, so every `ModelStore` backend sees the same
+    // non-negative limit/offset: SQLite's `LIMIT` treats a negative value as
+    // "unlimited" while `InMemoryStore` treats it as zero, and without this
+    // the two backends would page differently for the same query.
+    let limit = req
+        .get("limit")
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .max(0);
+    let offset = req
+        .get("offset")
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(0)
+        .max(0);
+
+    let query = SearchQuery {
+        q: req.get("q").cloned(),
+        keyword: req.get("keyword").cloned(),
+        limit,
+        offset,
+    };
+
+    match state.store.lock().unwrap().search(&query) {
+        Ok(results) => Json(results.into_iter().map(|r| {
+            serde_json::json!({
+                "cid": r.cid,
+                "title": r.title,
+                "description": r.description,
+                "keywords": r.keywords,
+            })
+        }).collect::<Vec<_>>()).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
 }
 
 fn index_response(cid: String, data: String) -> impl IntoResponse {
@@ -100,12 +281,18 @@ fn index_response_redirect(cid: String) -> impl IntoResponse {
 async fn model_handler(
     Path(ipfs_cid): Path<String>,
     req: Query<HashMap<String, String>>,
-    state: State<Arc<Mutex<Storage>>>,
+    headers: HeaderMap,
+    state: State<AppState>,
 ) -> impl IntoResponse {
     let zparam = req.get("z");
+    if zparam.is_some() {
+        if let Err(response) = authorize_write(&headers, &state) {
+            return response;
+        }
+    }
     let zblob = string_to_zblob(zparam);
 
-    let new_blob = state.lock().unwrap().create_or_retrieve(
+    let new_blob = state.store.lock().unwrap().create_or_retrieve(
         "pflow_models",
         &zblob.ipfs_cid,
         &zblob.base64_zipped,
@@ -119,7 +306,7 @@ async fn model_handler(
         return index_response_redirect(new_blob.ipfs_cid).into_response();
     }
 
-    let zblob = state.lock().unwrap()
+    let zblob = state.store.lock().unwrap()
         .get_by_cid("pflow_models", &*ipfs_cid)
         .unwrap_or(Option::from(Zblob::default()))
         .unwrap_or(Zblob::default());
@@ -139,10 +326,17 @@ fn string_to_zblob(data: Option<&String>) -> Zblob {
 
 async fn index_handler(
     req: Query<HashMap<String, String>>,
-    state: State<Arc<Mutex<Storage>>>,
+    headers: HeaderMap,
+    state: State<AppState>,
 ) -> impl IntoResponse {
-    let zblob = string_to_zblob(req.get("z"));
-    let new_blob = state.lock().unwrap().create_or_retrieve(
+    let zparam = req.get("z");
+    if zparam.is_some() {
+        if let Err(response) = authorize_write(&headers, &state) {
+            return response;
+        }
+    }
+    let zblob = string_to_zblob(zparam);
+    let new_blob = state.store.lock().unwrap().create_or_retrieve(
         "pflow_models",
         &zblob.ipfs_cid,
         &zblob.base64_zipped,
@@ -163,27 +357,56 @@ async fn index_handler(
 pub fn app() -> Router {
     let store = Storage::new("pflow.db").unwrap();
     store.create_tables().unwrap();
-    let state: Arc<Mutex<Storage>> = Arc::new(Mutex::new(store));
+    let auth: Arc<dyn ApiAuth> = match StaticKeyAuth::from_file("pflow_keys.txt") {
+        Ok(auth) => Arc::new(auth),
+        Err(_) => Arc::new(StaticKeyAuth::from_keys(std::iter::empty())),
+    };
+    app_with_state(AppState {
+        store: Arc::new(Mutex::new(store)),
+        auth,
+        rate_limiter: Arc::new(RateLimiter::default()),
+    })
+}
+
+/// Builds the router against an arbitrary `ModelStore`, so tests can run
+/// against an in-memory backend without touching the filesystem. Write
+/// routes reject every key, since no deployment-specific auth is configured.
+pub fn app_with_store(store: SharedStore) -> Router {
+    app_with_state(AppState {
+        store,
+        auth: Arc::new(StaticKeyAuth::from_keys(std::iter::empty())),
+        rate_limiter: Arc::new(RateLimiter::default()),
+    })
+}
 
+/// Builds the router against a fully-assembled `AppState`.
+pub fn app_with_state(state: AppState) -> Router {
     // Build route service
     Router::new()
         .route("/img/:ipfs_cid.svg", get(img_handler))
         .route("/src/:ipfs_cid.json", get(src_handler))
+        .route("/analyze/:ipfs_cid.json", get(analyze_handler))
+        .route("/search", get(search_handler))
         .route("/p/:ipfs_cid/", get(model_handler))
-        .route("/p/", get(get(index_handler)))
+        .route("/p/", get(get(index_handler)).post(upload_handler))
         .route("/p", get(|| async { Redirect::to("/p/") }))
         .route("/", get(|| async { Redirect::to("/p/") }))
         .layer(TraceLayer::new_for_http())
+        // Negotiates gzip/deflate via Accept-Encoding; skips bodies too small
+        // to be worth the round-trip.
+        .layer(CompressionLayer::new().compress_when(SizeAbove::new(256)))
         .with_state(state)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use pflow_metamodel::compression::unzip_encoded;
     use pflow_metamodel::petri_net::PetriNet;
     use crate::fixtures::INHIBIT_TEST;
-    use crate::server::string_to_zblob;
-    use crate::storage::Storage;
+    use crate::server::{app_with_store, string_to_zblob, SharedStore};
+    use crate::storage::{ModelStore, Storage};
 
     #[test]
     fn test_serve_by_ipfs_cid() {
@@ -215,4 +438,33 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_app_with_in_memory_store() {
+        use crate::storage::InMemoryStore;
+
+        let state: SharedStore = Arc::new(Mutex::new(InMemoryStore::default()));
+        state.lock().unwrap().create_tables().unwrap();
+        // Building the router must not touch the filesystem when backed by
+        // an in-memory store.
+        let _router = app_with_store(state.clone());
+
+        // No request is sent through the router here; the shared store handle
+        // it was built with is exercised directly: a model written through it
+        // is readable back through it.
+        let z = string_to_zblob(Option::from(&INHIBIT_TEST.to_string()));
+        let created = state.lock().unwrap().create_or_retrieve(
+            "pflow_models",
+            &z.ipfs_cid,
+            &z.base64_zipped,
+            &z.title,
+            &z.description,
+            &z.keywords,
+            &z.referrer,
+        ).unwrap();
+        assert_eq!(created.id, 1);
+
+        let fetched = state.lock().unwrap().get_by_cid("pflow_models", &z.ipfs_cid).unwrap();
+        assert_eq!(fetched, Some(created));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+
+/// A stored, content-addressed model blob and its searchable metadata.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Zblob {
+    pub id: i64,
+    pub ipfs_cid: String,
+    pub base64_zipped: String,
+    pub title: String,
+    pub description: String,
+    pub keywords: String,
+    pub referrer: String,
+}
+
+/// A `{cid, title, description, keywords}` summary returned by search, cheap
+/// enough to list in bulk without shipping the full compressed model.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelSummary {
+    pub cid: String,
+    pub title: String,
+    pub description: String,
+    pub keywords: String,
+}
+
+/// Search parameters for `ModelStore::search`. `q` matches free text across
+/// title/description/keywords; `keyword` narrows to keyword tags containing
+/// the given substring.
+#[derive(Clone, Debug, Default)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    pub keyword: Option<String>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// The storage operations the HTTP handlers actually need. Extracted so the
+/// server can run against an in-memory store in tests and a real database in
+/// production without the handlers knowing which.
+pub trait ModelStore: Send {
+    fn create_tables(&self) -> Result<(), Box<dyn Error>>;
+    fn reset_db(&self, drop_tables: bool) -> Result<(), Box<dyn Error>>;
+    fn get_by_cid(&self, table: &str, ipfs_cid: &str) -> Result<Option<Zblob>, Box<dyn Error>>;
+    #[allow(clippy::too_many_arguments)]
+    fn create_or_retrieve(
+        &self,
+        table: &str,
+        ipfs_cid: &str,
+        base64_zipped: &str,
+        title: &str,
+        description: &str,
+        keywords: &str,
+        referrer: &str,
+    ) -> Result<Zblob, Box<dyn Error>>;
+    fn search(&self, query: &SearchQuery) -> Result<Vec<ModelSummary>, Box<dyn Error>>;
+}
+
+/// SQLite-backed `ModelStore`, the default for local/single-node deployments.
+pub struct Storage {
+    conn: Connection,
+}
+
+impl Storage {
+    pub fn new(path: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Storage {
+            conn: Connection::open(path)?,
+        })
+    }
+}
+
+impl ModelStore for Storage {
+    fn create_tables(&self) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS pflow_models (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ipfs_cid TEXT UNIQUE NOT NULL,
+                base64_zipped TEXT NOT NULL,
+                title TEXT NOT NULL DEFAULT '',
+                description TEXT NOT NULL DEFAULT '',
+                keywords TEXT NOT NULL DEFAULT '',
+                referrer TEXT NOT NULL DEFAULT ''
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn reset_db(&self, drop_tables: bool) -> Result<(), Box<dyn Error>> {
+        if drop_tables {
+            self.conn.execute("DROP TABLE IF EXISTS pflow_models", [])?;
+        }
+        self.create_tables()
+    }
+
+    fn get_by_cid(&self, table: &str, ipfs_cid: &str) -> Result<Option<Zblob>, Box<dyn Error>> {
+        let sql = format!(
+            "SELECT id, ipfs_cid, base64_zipped, title, description, keywords, referrer
+             FROM {table} WHERE ipfs_cid = ?1"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut rows = stmt.query(params![ipfs_cid])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(Zblob {
+                id: row.get(0)?,
+                ipfs_cid: row.get(1)?,
+                base64_zipped: row.get(2)?,
+                title: row.get(3)?,
+                description: row.get(4)?,
+                keywords: row.get(5)?,
+                referrer: row.get(6)?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn create_or_retrieve(
+        &self,
+        table: &str,
+        ipfs_cid: &str,
+        base64_zipped: &str,
+        title: &str,
+        description: &str,
+        keywords: &str,
+        referrer: &str,
+    ) -> Result<Zblob, Box<dyn Error>> {
+        if let Some(existing) = self.get_by_cid(table, ipfs_cid)? {
+            return Ok(existing);
+        }
+
+        let sql = format!(
+            "INSERT INTO {table} (ipfs_cid, base64_zipped, title, description, keywords, referrer)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+        );
+        self.conn
+            .execute(&sql, params![ipfs_cid, base64_zipped, title, description, keywords, referrer])?;
+
+        Ok(Zblob {
+            id: self.conn.last_insert_rowid(),
+            ipfs_cid: ipfs_cid.to_string(),
+            base64_zipped: base64_zipped.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            keywords: keywords.to_string(),
+            referrer: referrer.to_string(),
+        })
+    }
+
+    fn search(&self, query: &SearchQuery) -> Result<Vec<ModelSummary>, Box<dyn Error>> {
+        let like = query.q.as_deref().map(|q| format!("%{q}%"));
+        let sql = "SELECT ipfs_cid, title, description, keywords FROM pflow_models
+             WHERE (?1 IS NULL OR title LIKE ?1 OR description LIKE ?1 OR keywords LIKE ?1)
+               AND (?2 IS NULL OR keywords LIKE ?2)
+             ORDER BY id DESC LIMIT ?3 OFFSET ?4";
+        let keyword_like = query.keyword.as_deref().map(|k| format!("%{k}%"));
+
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(
+            params![like, keyword_like, query.limit, query.offset],
+            |row| {
+                Ok(ModelSummary {
+                    cid: row.get(0)?,
+                    title: row.get(1)?,
+                    description: row.get(2)?,
+                    keywords: row.get(3)?,
+                })
+            },
+        )?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
+
+/// In-memory `ModelStore`, keyed by CID. `app()` always opens `pflow.db`;
+/// this backend exists so tests (and `app_with_store`) can exercise the
+/// request/response layer without touching the filesystem.
+#[derive(Default)]
+pub struct InMemoryStore {
+    by_cid: Mutex<HashMap<String, Zblob>>,
+    next_id: Mutex<i64>,
+}
+
+impl ModelStore for InMemoryStore {
+    fn create_tables(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn reset_db(&self, _drop_tables: bool) -> Result<(), Box<dyn Error>> {
+        self.by_cid.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn get_by_cid(&self, _table: &str, ipfs_cid: &str) -> Result<Option<Zblob>, Box<dyn Error>> {
+        Ok(self.by_cid.lock().unwrap().get(ipfs_cid).cloned())
+    }
+
+    fn create_or_retrieve(
+        &self,
+        table: &str,
+        ipfs_cid: &str,
+        base64_zipped: &str,
+        title: &str,
+        description: &str,
+        keywords: &str,
+        referrer: &str,
+    ) -> Result<Zblob, Box<dyn Error>> {
+        if let Some(existing) = self.get_by_cid(table, ipfs_cid)? {
+            return Ok(existing);
+        }
+
+        let mut next_id = self.next_id.lock().unwrap();
+        *next_id += 1;
+        let zblob = Zblob {
+            id: *next_id,
+            ipfs_cid: ipfs_cid.to_string(),
+            base64_zipped: base64_zipped.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            keywords: keywords.to_string(),
+            referrer: referrer.to_string(),
+        };
+        self.by_cid.lock().unwrap().insert(ipfs_cid.to_string(), zblob.clone());
+        Ok(zblob)
+    }
+
+    fn search(&self, query: &SearchQuery) -> Result<Vec<ModelSummary>, Box<dyn Error>> {
+        let by_cid = self.by_cid.lock().unwrap();
+        let mut matches: Vec<&Zblob> = by_cid
+            .values()
+            .filter(|z| matches_query(z, query))
+            .collect();
+        matches.sort_by(|a, b| b.id.cmp(&a.id));
+
+        Ok(matches
+            .into_iter()
+            .skip(query.offset.max(0) as usize)
+            .take(query.limit.max(0) as usize)
+            .map(|z| ModelSummary {
+                cid: z.ipfs_cid.clone(),
+                title: z.title.clone(),
+                description: z.description.clone(),
+                keywords: z.keywords.clone(),
+            })
+            .collect())
+    }
+}
+
+fn matches_query(zblob: &Zblob, query: &SearchQuery) -> bool {
+    let q_matches = query.q.as_deref().map_or(true, |q| {
+        let q = q.to_lowercase();
+        zblob.title.to_lowercase().contains(&q)
+            || zblob.description.to_lowercase().contains(&q)
+            || zblob.keywords.to_lowercase().contains(&q)
+    });
+    let keyword_matches = query
+        .keyword
+        .as_deref()
+        .map_or(true, |k| zblob.keywords.to_lowercase().contains(&k.to_lowercase()));
+    q_matches && keyword_matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_store_round_trips_create_and_get() {
+        let store = InMemoryStore::default();
+        store.create_tables().unwrap();
+
+        let created = store
+            .create_or_retrieve("pflow_models", "cid-1", "zipped", "title", "desc", "kw", "ref")
+            .unwrap();
+        assert_eq!(created.id, 1);
+
+        let fetched = store.get_by_cid("pflow_models", "cid-1").unwrap();
+        assert_eq!(fetched, Some(created.clone()));
+
+        // A second create_or_retrieve for the same CID returns the existing
+        // row rather than inserting a duplicate.
+        let again = store
+            .create_or_retrieve("pflow_models", "cid-1", "zipped", "title", "desc", "kw", "ref")
+            .unwrap();
+        assert_eq!(again, created);
+    }
+}
+
+#[cfg(feature = "postgres")]
+pub mod postgres {
+    use std::error::Error;
+    use std::sync::Mutex;
+
+    use postgres::{Client, NoTls};
+
+    use super::{ModelStore, ModelSummary, SearchQuery, Zblob};
+
+    /// Postgres-backed `ModelStore` for multi-node deployments.
+    pub struct PostgresStore {
+        client: Mutex<Client>,
+    }
+
+    impl PostgresStore {
+        pub fn connect(conninfo: &str) -> Result<Self, Box<dyn Error>> {
+            Ok(PostgresStore {
+                client: Mutex::new(Client::connect(conninfo, NoTls)?),
+            })
+        }
+    }
+
+    impl ModelStore for PostgresStore {
+        fn create_tables(&self) -> Result<(), Box<dyn Error>> {
+            self.client.lock().unwrap().batch_execute(
+                "CREATE TABLE IF NOT EXISTS pflow_models (
+                    id BIGSERIAL PRIMARY KEY,
+                    ipfs_cid TEXT UNIQUE NOT NULL,
+                    base64_zipped TEXT NOT NULL,
+                    title TEXT NOT NULL DEFAULT '',
+                    description TEXT NOT NULL DEFAULT '',
+                    keywords TEXT NOT NULL DEFAULT '',
+                    referrer TEXT NOT NULL DEFAULT ''
+                )",
+            )?;
+            Ok(())
+        }
+
+        fn reset_db(&self, drop_tables: bool) -> Result<(), Box<dyn Error>> {
+            if drop_tables {
+                self.client.lock().unwrap().batch_execute("DROP TABLE IF EXISTS pflow_models")?;
+            }
+            self.create_tables()
+        }
+
+        fn get_by_cid(&self, table: &str, ipfs_cid: &str) -> Result<Option<Zblob>, Box<dyn Error>> {
+            let sql = format!(
+                "SELECT id, ipfs_cid, base64_zipped, title, description, keywords, referrer
+                 FROM {table} WHERE ipfs_cid = $1"
+            );
+            let row = self.client.lock().unwrap().query_opt(&sql, &[&ipfs_cid])?;
+            Ok(row.map(|row| Zblob {
+                id: row.get(0),
+                ipfs_cid: row.get(1),
+                base64_zipped: row.get(2),
+                title: row.get(3),
+                description: row.get(4),
+                keywords: row.get(5),
+                referrer: row.get(6),
+            }))
+        }
+
+        fn create_or_retrieve(
+            &self,
+            table: &str,
+            ipfs_cid: &str,
+            base64_zipped: &str,
+            title: &str,
+            description: &str,
+            keywords: &str,
+            referrer: &str,
+        ) -> Result<Zblob, Box<dyn Error>> {
+            if let Some(existing) = self.get_by_cid(table, ipfs_cid)? {
+                return Ok(existing);
+            }
+
+            let sql = format!(
+                "INSERT INTO {table} (ipfs_cid, base64_zipped, title, description, keywords, referrer)
+                 VALUES ($1, $2, $3, $4, $5, $6)"
+            );
+            self.client.lock().unwrap()
+                .execute(&sql, &[&ipfs_cid, &base64_zipped, &title, &description, &keywords, &referrer])?;
+
+            self.get_by_cid(table, ipfs_cid)?
+                .ok_or_else(|| "insert succeeded but row was not found".into())
+        }
+
+        fn search(&self, query: &SearchQuery) -> Result<Vec<ModelSummary>, Box<dyn Error>> {
+            let like = query.q.as_ref().map(|q| format!("%{q}%"));
+            let keyword_like = query.keyword.as_ref().map(|k| format!("%{k}%"));
+            let rows = self.client.lock().unwrap().query(
+                "SELECT ipfs_cid, title, description, keywords FROM pflow_models
+                 WHERE ($1::text IS NULL OR title ILIKE $1 OR description ILIKE $1 OR keywords ILIKE $1)
+                   AND ($2::text IS NULL OR keywords ILIKE $2)
+                 ORDER BY id DESC LIMIT $3 OFFSET $4",
+                &[&like, &keyword_like, &query.limit, &query.offset],
+            )?;
+
+            Ok(rows
+                .into_iter()
+                .map(|row| ModelSummary {
+                    cid: row.get(0),
+                    title: row.get(1),
+                    description: row.get(2),
+                    keywords: row.get(3),
+                })
+                .collect())
+        }
+    }
+}